@@ -1,5 +1,6 @@
 use std::io::Write;
 use std::sync::Arc;
+use std::time::Duration;
 
 use bytes::Bytes;
 use log::{info, warn};
@@ -12,7 +13,9 @@ use tokio::sync::mpsc::{self, Sender, UnboundedSender};
 use webrtc::{
     api::media_engine::{MIME_TYPE_OPUS, MediaEngine},
     data_channel::RTCDataChannel,
+    media::Sample,
     rtp_transceiver::rtp_codec::{RTCRtpCodecCapability, RTCRtpCodecParameters, RTPCodecType},
+    track::track_local::track_local_static_sample::TrackLocalStaticSample,
 };
 
 pub fn register_audio_codecs(media_engine: &mut MediaEngine) -> Result<(), webrtc::Error> {
@@ -50,6 +53,68 @@ impl OpusTrackSampleAudioDecoder {
     }
 }
 
+/// Builds the Ogg `OpusHead` identification header for the given stream layout.
+///
+/// Mono/stereo streams use mapping family 0, which has no explicit channel
+/// mapping table. Anything with more channels needs mapping family 1, which
+/// appends the stream count, coupled-stream count, and a per-channel
+/// mapping table so multichannel decoders know how to route each Opus
+/// stream's output channels. See RFC 7845 section 5.1.1.
+fn build_opus_head(config: &OpusMultistreamConfig) -> Vec<u8> {
+    let mut header = Vec::new();
+    header.extend_from_slice(b"OpusHead");
+    header.push(1); // Version
+    header.push(config.channel_count as u8); // Channels
+    header.extend_from_slice(&0u16.to_le_bytes()); // Pre-skip
+    header.extend_from_slice(&config.sample_rate.to_le_bytes()); // Sample rate
+    header.extend_from_slice(&0u16.to_le_bytes()); // Gain
+
+    if config.channel_count > 2 {
+        header.push(1); // Mapping family 1: channel mapping table follows
+        header.push(config.stream_count as u8);
+        header.push(config.coupled_stream_count as u8);
+        header.extend_from_slice(&config.mapping[..config.channel_count as usize]);
+    } else {
+        header.push(0); // Mapping family 0: no table
+    }
+
+    header
+}
+
+/// Picks the `AudioConfig` that matches a negotiated channel count, falling
+/// back to stereo for layouts Moonlight doesn't have a named config for.
+fn audio_config_for_channels(channel_count: u32) -> AudioConfig {
+    match channel_count {
+        1 => AudioConfig::MONO,
+        6 => AudioConfig::SURROUND_5_1,
+        8 => AudioConfig::SURROUND_7_1,
+        _ => AudioConfig::STEREO,
+    }
+}
+
+const SUPPORTED_SAMPLE_RATES: &[u32] = &[80000, 12000, 16000, 24000, 48000];
+
+/// Warns if the negotiated stream looks like it'll cause problems. Shared by
+/// the Opus `AudioDecoder::setup` impls in this file; the AAC decoder ignores
+/// the negotiated config and doesn't call it.
+fn warn_on_unexpected_negotiation(
+    requested: AudioConfig,
+    negotiated: AudioConfig,
+    stream_config: &OpusMultistreamConfig,
+) {
+    if !SUPPORTED_SAMPLE_RATES.contains(&stream_config.sample_rate) {
+        warn!(
+            "[Stream] Audio could have problems because of the sample rate, Selected: {}, Expected one of: {SUPPORTED_SAMPLE_RATES:?}",
+            stream_config.sample_rate
+        );
+    }
+    if negotiated != requested {
+        warn!(
+            "[Stream] A different audio configuration than requested was selected, Expected: {requested:?}, Found: {negotiated:?}",
+        );
+    }
+}
+
 struct VecSender(UnboundedSender<Vec<u8>>);
 impl Write for VecSender {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
@@ -71,22 +136,10 @@ impl AudioDecoder for OpusTrackSampleAudioDecoder {
         _ar_flags: i32,
     ) -> i32 {
         info!("[Stream] Audio setup: {audio_config:?}, {stream_config:?}");
-
-        const SUPPORTED_SAMPLE_RATES: &[u32] = &[80000, 12000, 16000, 24000, 48000];
-        if !SUPPORTED_SAMPLE_RATES.contains(&stream_config.sample_rate) {
-            warn!(
-                "[Stream] Audio could have problems because of the sample rate, Selected: {}, Expected one of: {SUPPORTED_SAMPLE_RATES:?}",
-                stream_config.sample_rate
-            );
-        }
-        if audio_config != self.config() {
-            warn!(
-                "[Stream] A different audio configuration than requested was selected, Expected: {:?}, Found: {audio_config:?}",
-                self.config()
-            );
-        }
+        warn_on_unexpected_negotiation(self.config(), audio_config, &stream_config);
 
         let samples_per_frame = stream_config.samples_per_frame as u64;
+        let id_header = build_opus_head(&stream_config);
         self.config = Some(stream_config);
 
         let (sender, mut receiver) = mpsc::channel::<Bytes>(50);
@@ -100,16 +153,6 @@ impl AudioDecoder for OpusTrackSampleAudioDecoder {
             let serial = 12345;
             let mut granule_pos = 0;
 
-            // Write ID Header
-            let mut id_header = Vec::new();
-            id_header.extend_from_slice(b"OpusHead");
-            id_header.push(1); // Version
-            id_header.push(2); // Channels
-            id_header.extend_from_slice(&0u16.to_le_bytes()); // Pre-skip
-            id_header.extend_from_slice(&48000u32.to_le_bytes()); // Sample rate
-            id_header.extend_from_slice(&0u16.to_le_bytes()); // Gain
-            id_header.push(0); // Mapping family
-
             if let Err(e) = writer.write_packet(
                 id_header,
                 serial,
@@ -178,6 +221,355 @@ impl AudioDecoder for OpusTrackSampleAudioDecoder {
     }
 
     fn config(&self) -> AudioConfig {
-        AudioConfig::STEREO
+        match &self.config {
+            Some(config) => audio_config_for_channels(config.channel_count),
+            None => AudioConfig::STEREO,
+        }
+    }
+}
+
+/// Decodes Opus the same way as [`OpusTrackSampleAudioDecoder`], but hands
+/// each decoded frame to a real `TrackLocalStaticSample` on an audio
+/// transceiver instead of muxing it into an Ogg stream over a data channel.
+/// This lets the browser's own RTP jitter buffer, PLC, and A/V sync handle
+/// playback instead of a hand-rolled container on top of a data channel.
+pub struct OpusTrackRtpAudioDecoder {
+    track: Arc<TrackLocalStaticSample>,
+    sender: Option<Sender<Bytes>>,
+    config: Option<OpusMultistreamConfig>,
+}
+
+impl OpusTrackRtpAudioDecoder {
+    pub fn new(track: Arc<TrackLocalStaticSample>) -> Self {
+        Self {
+            track,
+            sender: None,
+            config: None,
+        }
+    }
+}
+
+impl AudioDecoder for OpusTrackRtpAudioDecoder {
+    fn setup(
+        &mut self,
+        audio_config: AudioConfig,
+        stream_config: OpusMultistreamConfig,
+        _ar_flags: i32,
+    ) -> i32 {
+        info!("[Stream] Audio setup (RTP): {audio_config:?}, {stream_config:?}");
+        warn_on_unexpected_negotiation(self.config(), audio_config, &stream_config);
+
+        let frame_duration = Duration::from_secs_f64(
+            stream_config.samples_per_frame as f64 / stream_config.sample_rate as f64,
+        );
+        self.config = Some(stream_config);
+
+        let (sender, mut receiver) = mpsc::channel::<Bytes>(50);
+        self.sender = Some(sender);
+
+        let track = self.track.clone();
+
+        tokio::spawn(async move {
+            while let Some(data) = receiver.recv().await {
+                let sample = Sample {
+                    data,
+                    duration: frame_duration,
+                    ..Default::default()
+                };
+
+                if let Err(e) = track.write_sample(&sample).await {
+                    warn!("Failed to write audio sample: {:?}", e);
+                }
+            }
+        });
+
+        0
+    }
+
+    fn start(&mut self) {}
+
+    fn stop(&mut self) {}
+
+    fn decode_and_play_sample(&mut self, data: &[u8]) {
+        if let Some(sender) = &self.sender {
+            let data = Bytes::copy_from_slice(data);
+            let _ = sender.blocking_send(data);
+        }
+    }
+
+    fn config(&self) -> AudioConfig {
+        match &self.config {
+            Some(config) => audio_config_for_channels(config.channel_count),
+            None => AudioConfig::STEREO,
+        }
+    }
+}
+
+const AAC_SAMPLING_FREQUENCIES: [u32; 13] = [
+    96000, 88200, 64000, 48000, 44100, 32000, 24000, 22050, 16000, 12000, 11025, 8000, 7350,
+];
+
+/// The fields of an MPEG-4 AudioSpecificConfig (ISO/IEC 14496-3 section 1.6.2.1)
+/// needed to frame raw AAC access units as ADTS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AacAudioSpecificConfig {
+    audio_object_type: u8,
+    sampling_frequency_index: u8,
+    sample_rate: u32,
+    channel_configuration: u8,
+}
+
+impl AacAudioSpecificConfig {
+    /// Parses a 2-byte (or, with the sampling-frequency escape, 5-byte)
+    /// AudioSpecificConfig: a 5-bit audio object type, a 4-bit
+    /// sampling-frequency index that escapes to an explicit 24-bit rate when
+    /// it's `0xF`, and a 4-bit channel configuration.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let mut reader = BitReader::new(data);
+
+        let audio_object_type = reader.read_bits(5)? as u8;
+        let sampling_frequency_index = reader.read_bits(4)? as u8;
+        let sample_rate = if sampling_frequency_index == 0x0F {
+            reader.read_bits(24)?
+        } else {
+            *AAC_SAMPLING_FREQUENCIES.get(sampling_frequency_index as usize)?
+        };
+        let channel_configuration = reader.read_bits(4)? as u8;
+
+        Some(Self {
+            audio_object_type,
+            sampling_frequency_index,
+            sample_rate,
+            channel_configuration,
+        })
+    }
+
+    /// The `sampling_frequency_index` ADTS expects. ADTS has no escape for
+    /// an explicit rate, so an escaped ASC falls back to the closest entry
+    /// in the fixed table.
+    fn adts_sampling_frequency_index(&self) -> u8 {
+        if self.sampling_frequency_index != 0x0F {
+            return self.sampling_frequency_index;
+        }
+
+        AAC_SAMPLING_FREQUENCIES
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, &rate)| rate.abs_diff(self.sample_rate))
+            .map_or(3, |(index, _)| index as u8) // default to 48000 if somehow empty
+    }
+
+    /// The 2-bit ADTS `profile` field, which only has room for object types
+    /// 1-4 (Main/LC/SSR/LTP). Extension object types like SBR/PS (HE-AAC,
+    /// HE-AACv2) can't be represented there, so they're signalled as LC,
+    /// matching how other ADTS muxers frame HE-AAC content.
+    fn adts_profile(&self) -> u8 {
+        match self.audio_object_type {
+            1..=4 => self.audio_object_type - 1,
+            _ => 1, // LC
+        }
+    }
+
+    /// Samples per frame: 960 for the HE-AACv2 (SBR+PS) object type used
+    /// with short frames, 1024 otherwise.
+    pub fn samples_per_frame(&self) -> u16 {
+        if self.audio_object_type == 29 { 960 } else { 1024 }
+    }
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    bit_pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, bit_pos: 0 }
+    }
+
+    fn read_bits(&mut self, count: usize) -> Option<u32> {
+        let mut value: u32 = 0;
+        for _ in 0..count {
+            let byte = *self.data.get(self.bit_pos / 8)?;
+            let bit = (byte >> (7 - self.bit_pos % 8)) & 1;
+            value = (value << 1) | u32::from(bit);
+            self.bit_pos += 1;
+        }
+        Some(value)
+    }
+}
+
+/// Builds a 7-byte ADTS header (no CRC) for an AAC access unit of `payload_len` bytes.
+fn build_adts_header(config: &AacAudioSpecificConfig, payload_len: usize) -> [u8; 7] {
+    let mut header = [0u8; 7];
+    let mut bit_pos = 0;
+    let mut write_bits = |value: u32, count: usize| {
+        for i in (0..count).rev() {
+            let bit = ((value >> i) & 1) as u8;
+            header[bit_pos / 8] |= bit << (7 - bit_pos % 8);
+            bit_pos += 1;
+        }
+    };
+
+    let frame_length = (7 + payload_len).min(0x1FFF); // 13-bit field, header + payload
+    if frame_length < 7 + payload_len {
+        warn!(
+            "[Stream] AAC access unit of {payload_len} bytes doesn't fit in the 13-bit ADTS frame length field; truncating"
+        );
+    }
+
+    write_bits(0xFFF, 12); // syncword
+    write_bits(0, 1); // MPEG version (0 = MPEG-4)
+    write_bits(0, 2); // layer
+    write_bits(1, 1); // protection_absent (no CRC)
+    write_bits(u32::from(config.adts_profile()), 2);
+    write_bits(u32::from(config.adts_sampling_frequency_index()), 4);
+    write_bits(0, 1); // private_bit
+    write_bits(u32::from(config.channel_configuration), 3);
+    write_bits(0, 1); // original/copy
+    write_bits(0, 1); // home
+    write_bits(0, 1); // copyright_id_bit
+    write_bits(0, 1); // copyright_id_start
+    write_bits(frame_length as u32, 13);
+    write_bits(0x7FF, 11); // adts_buffer_fullness (VBR)
+    write_bits(0, 2); // number_of_raw_data_blocks_in_frame - 1
+
+    header
+}
+
+/// Decodes AAC by wrapping each raw access unit in an ADTS header and
+/// shipping it over the data channel, the same transport
+/// [`OpusTrackSampleAudioDecoder`] uses for Ogg Opus. Moonlight's audio
+/// renderer callbacks are Opus-shaped (`setup` negotiates an
+/// `OpusMultistreamConfig`), so this decoder takes its real
+/// AudioSpecificConfig out of band at construction time instead. `setup`
+/// only logs the (irrelevant) negotiated config and starts the task that
+/// sends framed samples over the data channel.
+pub struct AacTrackSampleAudioDecoder {
+    channel: Arc<RTCDataChannel>,
+    sender: Option<Sender<Bytes>>,
+    config: Option<AacAudioSpecificConfig>,
+}
+
+impl AacTrackSampleAudioDecoder {
+    pub fn new(channel: Arc<RTCDataChannel>, audio_specific_config: &[u8]) -> Self {
+        let config = AacAudioSpecificConfig::parse(audio_specific_config);
+        if config.is_none() {
+            warn!(
+                "[Stream] Failed to parse AAC AudioSpecificConfig: {audio_specific_config:02x?}"
+            );
+        }
+
+        Self {
+            channel,
+            sender: None,
+            config,
+        }
+    }
+}
+
+/// Maps an AAC `channel_configuration` (ISO/IEC 14496-3 table 1.19) to the
+/// channel count `audio_config_for_channels` expects.
+fn aac_channel_count(channel_configuration: u8) -> u32 {
+    match channel_configuration {
+        7 => 8, // 7.1
+        other => u32::from(other),
+    }
+}
+
+impl AudioDecoder for AacTrackSampleAudioDecoder {
+    fn setup(
+        &mut self,
+        audio_config: AudioConfig,
+        stream_config: OpusMultistreamConfig,
+        _ar_flags: i32,
+    ) -> i32 {
+        info!(
+            "[Stream] Audio setup (AAC, negotiated Opus config ignored): {audio_config:?}, {stream_config:?}"
+        );
+
+        let (sender, mut receiver) = mpsc::channel::<Bytes>(50);
+        self.sender = Some(sender);
+
+        let channel = self.channel.clone();
+        tokio::spawn(async move {
+            while let Some(frame) = receiver.recv().await {
+                if let Err(e) = channel.send(&frame).await {
+                    warn!("Failed to send ADTS-framed AAC sample: {:?}", e);
+                }
+            }
+        });
+
+        0
+    }
+
+    fn start(&mut self) {}
+
+    fn stop(&mut self) {}
+
+    fn decode_and_play_sample(&mut self, data: &[u8]) {
+        let Some(config) = self.config else {
+            warn!("[Stream] Dropping AAC frame received before a valid AudioSpecificConfig");
+            return;
+        };
+        let Some(sender) = &self.sender else {
+            return;
+        };
+
+        let header = build_adts_header(&config, data.len());
+        let mut framed = Vec::with_capacity(header.len() + data.len());
+        framed.extend_from_slice(&header);
+        framed.extend_from_slice(data);
+
+        let _ = sender.blocking_send(Bytes::from(framed));
+    }
+
+    fn config(&self) -> AudioConfig {
+        match &self.config {
+            Some(config) => audio_config_for_channels(aac_channel_count(config.channel_configuration)),
+            None => AudioConfig::STEREO,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_audio_specific_config_stereo_48khz() {
+        // audio_object_type=2 (AAC LC), sampling_frequency_index=3 (48000), channel_configuration=2
+        let data = [0b0001_0001, 0b1001_0000];
+        let config = AacAudioSpecificConfig::parse(&data).unwrap();
+
+        assert_eq!(config.audio_object_type, 2);
+        assert_eq!(config.sampling_frequency_index, 3);
+        assert_eq!(config.sample_rate, 48000);
+        assert_eq!(config.channel_configuration, 2);
+        assert_eq!(config.samples_per_frame(), 1024);
+    }
+
+    #[test]
+    fn test_parse_audio_specific_config_he_aac_v2_short_frame() {
+        // audio_object_type=29 (HE-AACv2), sampling_frequency_index=8 (16000), channel_configuration=1
+        let data = [0b1110_1100, 0b0000_1000];
+        let config = AacAudioSpecificConfig::parse(&data).unwrap();
+
+        assert_eq!(config.audio_object_type, 29);
+        assert_eq!(config.sample_rate, 16000);
+        assert_eq!(config.samples_per_frame(), 960);
+    }
+
+    #[test]
+    fn test_adts_header_frame_length_includes_header() {
+        let config = AacAudioSpecificConfig::parse(&[0b0001_0001, 0b1001_0000]).unwrap();
+        let header = build_adts_header(&config, 100);
+
+        assert_eq!(&header[..2], &[0xFF, 0xF1]); // syncword + no CRC
+        let frame_length = (u32::from(header[3] & 0x03) << 11)
+            | (u32::from(header[4]) << 3)
+            | (u32::from(header[5]) >> 5);
+        assert_eq!(frame_length, 107);
     }
 }
@@ -1,6 +1,6 @@
 use std::ops::Range;
 
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AnnexBStartCode {
@@ -32,77 +32,272 @@ pub struct AnnexBData {
     pub full: Bytes,
 }
 
+impl AnnexBData {
+    fn header(&self) -> &[u8] {
+        &self.full[self.payload_range.clone()]
+    }
+
+    /// Decodes the NAL header. The header layout differs between codecs, so
+    /// the caller must say which one this bitstream is.
+    pub fn nal_unit_type(&self, codec: AnnexBCodec) -> NalUnitType {
+        match codec {
+            AnnexBCodec::H264 => NalUnitType::H264(H264NalUnitType::from_raw(self.header()[0] & 0x1F)),
+            AnnexBCodec::H265 => {
+                NalUnitType::H265(H265NalUnitType::from_raw((self.header()[0] >> 1) & 0x3F))
+            }
+        }
+    }
+
+    pub fn is_keyframe(&self, codec: AnnexBCodec) -> bool {
+        self.nal_unit_type(codec).is_keyframe()
+    }
+
+    pub fn is_parameter_set(&self, codec: AnnexBCodec) -> bool {
+        self.nal_unit_type(codec).is_parameter_set()
+    }
+
+    /// Strips `00 00 03` emulation-prevention bytes from the NAL payload
+    /// (the bytes after the NAL header) to recover the RBSP for downstream
+    /// bitstream reading.
+    pub fn rbsp(&self, codec: AnnexBCodec) -> Vec<u8> {
+        let header_len = codec.header_len();
+        let payload = &self.header()[header_len.min(self.header().len())..];
+        strip_emulation_prevention(payload)
+    }
+}
+
+/// Strips `00 00 03` emulation-prevention bytes, as inserted into Annex B
+/// bitstreams to stop the encoded RBSP from ever containing a byte sequence
+/// that looks like a start code.
+fn strip_emulation_prevention(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut zero_run = 0;
+
+    let mut i = 0;
+    while i < data.len() {
+        let byte = data[i];
+        if zero_run >= 2 && byte == 0x03 {
+            zero_run = 0;
+            i += 1;
+            continue;
+        }
+
+        out.push(byte);
+        zero_run = if byte == 0 { zero_run + 1 } else { 0 };
+        i += 1;
+    }
+
+    out
+}
+
+/// Which Annex B codec a bitstream is encoded with. The NAL header layout
+/// (and therefore the `nal_unit_type` field) differs between the two.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnexBCodec {
+    H264,
+    H265,
+}
+
+impl AnnexBCodec {
+    fn header_len(&self) -> usize {
+        match self {
+            Self::H264 => 1,
+            Self::H265 => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NalUnitType {
+    H264(H264NalUnitType),
+    H265(H265NalUnitType),
+}
+
+impl NalUnitType {
+    pub fn is_keyframe(&self) -> bool {
+        match self {
+            Self::H264(t) => matches!(t, H264NalUnitType::IdrSlice),
+            Self::H265(t) => matches!(
+                t,
+                H265NalUnitType::IdrWRadl | H265NalUnitType::IdrNLp | H265NalUnitType::Cra
+            ),
+        }
+    }
+
+    pub fn is_parameter_set(&self) -> bool {
+        match self {
+            Self::H264(t) => matches!(t, H264NalUnitType::Sps | H264NalUnitType::Pps),
+            Self::H265(t) => matches!(
+                t,
+                H265NalUnitType::VpsNut | H265NalUnitType::SpsNut | H265NalUnitType::PpsNut
+            ),
+        }
+    }
+}
+
+/// H.264 (Rec. ITU-T H.264 table 7-1) `nal_unit_type` values relevant to a
+/// WebRTC sender. Anything not called out explicitly is kept as `Other`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum H264NalUnitType {
+    NonIdrSlice,
+    PartitionA,
+    PartitionB,
+    PartitionC,
+    IdrSlice,
+    Sei,
+    Sps,
+    Pps,
+    AccessUnitDelimiter,
+    EndOfSequence,
+    EndOfStream,
+    FillerData,
+    Other(u8),
+}
+
+impl H264NalUnitType {
+    fn from_raw(nal_unit_type: u8) -> Self {
+        match nal_unit_type {
+            1 => Self::NonIdrSlice,
+            2 => Self::PartitionA,
+            3 => Self::PartitionB,
+            4 => Self::PartitionC,
+            5 => Self::IdrSlice,
+            6 => Self::Sei,
+            7 => Self::Sps,
+            8 => Self::Pps,
+            9 => Self::AccessUnitDelimiter,
+            10 => Self::EndOfSequence,
+            11 => Self::EndOfStream,
+            12 => Self::FillerData,
+            other => Self::Other(other),
+        }
+    }
+}
+
+/// H.265 (Rec. ITU-T H.265 table 7-1) `nal_unit_type` values relevant to a
+/// WebRTC sender. Anything not called out explicitly is kept as `Other`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum H265NalUnitType {
+    TrailN,
+    TrailR,
+    IdrWRadl,
+    IdrNLp,
+    Cra,
+    VpsNut,
+    SpsNut,
+    PpsNut,
+    AudNut,
+    PrefixSeiNut,
+    SuffixSeiNut,
+    Other(u8),
+}
+
+impl H265NalUnitType {
+    fn from_raw(nal_unit_type: u8) -> Self {
+        match nal_unit_type {
+            0 => Self::TrailN,
+            1 => Self::TrailR,
+            19 => Self::IdrWRadl,
+            20 => Self::IdrNLp,
+            21 => Self::Cra,
+            32 => Self::VpsNut,
+            33 => Self::SpsNut,
+            34 => Self::PpsNut,
+            35 => Self::AudNut,
+            39 => Self::PrefixSeiNut,
+            40 => Self::SuffixSeiNut,
+            other => Self::Other(other),
+        }
+    }
+}
+
+/// Splits an Annex B bitstream into individual NAL units.
+///
+/// `AnnexBSplitter` can be used two ways:
+/// - One-shot, via [`AnnexBSplitter::new`]: the whole bitstream is already
+///   available, and `next()` is called until it returns `None`.
+/// - Streaming, via [`AnnexBSplitter::new_streaming`] and [`push`](Self::push):
+///   fragments (e.g. RTP-depacketized chunks) arrive over time and a NAL may
+///   straddle a fragment boundary, including the start code itself. `next()`
+///   only yields a NAL once the start code that terminates it has actually
+///   been observed; call [`flush`](Self::flush) at end-of-stream to emit the
+///   final buffered NAL, whose end is otherwise unknowable.
 pub struct AnnexBSplitter {
-    data: Bytes,
-    offset: usize,
+    buffer: BytesMut,
+    finished: bool,
 }
 
 impl AnnexBSplitter {
     pub fn new(data: Bytes) -> Self {
+        let mut splitter = Self::new_streaming();
+        splitter.push(data);
+        splitter.finished = true;
+        splitter
+    }
+
+    /// Starts a splitter with no data yet, for incremental use with [`push`](Self::push).
+    pub fn new_streaming() -> Self {
         Self {
-            data,
-            offset: 0,
+            buffer: BytesMut::new(),
+            finished: false,
         }
     }
 
     pub fn reset(&mut self, data: Bytes) {
-        self.data = data;
-        self.offset = 0;
+        self.buffer.clear();
+        self.buffer.extend_from_slice(&data);
+        self.finished = true;
+    }
+
+    /// Appends a fragment to the rolling buffer. A NAL (or even its start
+    /// code) may be split across this and a previous/following `push`.
+    pub fn push(&mut self, data: Bytes) {
+        self.buffer.extend_from_slice(&data);
+    }
+
+    /// Marks the stream as ended and returns the final buffered NAL, if any.
+    /// Its end would otherwise be ambiguous: without a following start code,
+    /// a push-mode splitter can't tell a NAL still in flight from one that's
+    /// simply done.
+    pub fn flush(&mut self) -> Option<AnnexBData> {
+        self.finished = true;
+        self.next()
     }
 
     pub fn next(&mut self) -> Option<AnnexBData> {
-        if self.offset >= self.data.len() {
+        if self.buffer.is_empty() {
             return None;
         }
 
-        let current_slice = &self.data[self.offset..];
-        let (start_code, sc_len) = if current_slice.starts_with(&[0, 0, 0, 1]) {
-            (AnnexBStartCode::B4, 4)
-        } else if current_slice.starts_with(&[0, 0, 1]) {
-            (AnnexBStartCode::B3, 3)
-        } else {
-            // Should verify if we always start with a start code in valid Annex B
-            // If not, we might need to scan for the first one?
-            // Assuming we are at a start code or start of stream
-            
-            // If we are not at a start code, we scan for one to start?
-            // The original implementation buffered bytes until it found one.
-            // Let's scan.
-            match find_start_code(current_slice) {
-                Some((found_offset, sc, len)) => {
-                    self.offset += found_offset;
-                    (sc, len)
-                }
-                None => {
-                    // No start code found in remaining data.
-                    // This might be garbage or end of stream.
-                    self.offset = self.data.len();
-                    return None;
-                }
+        let (start_off, start_code, sc_len) = match find_start_code(&self.buffer) {
+            StartCodeScan::Found { offset, code, len } => (offset, code, len),
+            StartCodeScan::NotFound if self.finished => {
+                // Garbage with no start code, and no more data is coming.
+                self.buffer.clear();
+                return None;
+            }
+            StartCodeScan::NotFound => {
+                // No start code in what we have yet, but more data may still
+                // bring one. Keep the buffer and wait rather than discarding
+                // a NAL (or start code) that's only partially arrived.
+                return None;
             }
         };
 
-        // We are at a start code.
-        let payload_start = self.offset + sc_len;
-        
-        // Find next start code to determine end of this NAL
-        let next_sc_offset = if payload_start < self.data.len() {
-             find_start_code(&self.data[payload_start..]).map(|(off, _, _)| payload_start + off)
-        } else {
-            None
+        // Drop any leading garbage before the start code; it's never part of a NAL.
+        let _ = self.buffer.split_to(start_off);
+
+        let payload_start = sc_len;
+        let payload_end = match find_start_code(&self.buffer[payload_start..]) {
+            StartCodeScan::Found { offset, .. } => payload_start + offset,
+            StartCodeScan::NotFound if self.finished => self.buffer.len(),
+            StartCodeScan::NotFound => return None,
         };
 
-        let payload_end = next_sc_offset.unwrap_or(self.data.len());
-        
-        // Construct the NAL data
-        // We return the slice including the start code
-        let nal_len = payload_end - self.offset;
-        let full = self.data.slice(self.offset..payload_end);
-        
-        self.offset = payload_end;
+        let full = self.buffer.split_to(payload_end).freeze();
 
         Some(AnnexBData {
-            payload_range: sc_len..nal_len,
+            payload_range: sc_len..payload_end,
             start_code,
             start_code_range: 0..sc_len,
             full,
@@ -110,32 +305,43 @@ impl AnnexBSplitter {
     }
 }
 
-fn find_start_code(data: &[u8]) -> Option<(usize, AnnexBStartCode, usize)> {
+enum StartCodeScan {
+    Found {
+        offset: usize,
+        code: AnnexBStartCode,
+        len: usize,
+    },
+    /// No complete start code fits in the scanned data. In streaming mode
+    /// this doesn't mean there isn't one — up to three trailing zero bytes
+    /// (the prefix of a split `00 00 00 01`) are retained unexamined rather
+    /// than wrongly classified as payload, so the caller waits for more data.
+    NotFound,
+}
+
+fn find_start_code(data: &[u8]) -> StartCodeScan {
     let mut i = 0;
-    while i < data.len().saturating_sub(2) {
+    while i + 2 < data.len() {
         if data[i] == 0 && data[i+1] == 0 {
             if data[i+2] == 1 {
                 // Found 00 00 01
                 // Check if it was 00 00 00 01
                 if i > 0 && data[i-1] == 0 {
-                    return Some((i-1, AnnexBStartCode::B4, 4));
+                    return StartCodeScan::Found { offset: i - 1, code: AnnexBStartCode::B4, len: 4 };
                 } else {
-                    return Some((i, AnnexBStartCode::B3, 3));
+                    return StartCodeScan::Found { offset: i, code: AnnexBStartCode::B3, len: 3 };
                 }
             } else if data[i+2] == 0 {
                 // 00 00 00 ... might be start of 00 00 00 01
-                if i + 3 < data.len() && data[i+3] == 1 {
-                    return Some((i, AnnexBStartCode::B4, 4));
-                }
-                // Continue searching from i+1 (optimization: i+3?)
-                // If we have 00 00 00 00, next check at i+1 sees 00 00 00.
-                i += 1; 
+                // Continue searching from i+1: if we have 00 00 00 00, the
+                // next check at i+1 sees 00 00 00 again.
+                i += 1;
                 continue;
             }
         }
         i += 1;
     }
-    None
+
+    StartCodeScan::NotFound
 }
 
 #[cfg(test)]
@@ -209,4 +415,127 @@ mod tests {
         assert_eq!(nal.start_code, AnnexBStartCode::B3);
         assert_eq!(&nal.full[nal.payload_range.clone()], &[0x42]);
     }
+
+    #[test]
+    fn test_streaming_waits_for_terminating_start_code() {
+        let mut splitter = AnnexBSplitter::new_streaming();
+        splitter.push(Bytes::from_static(&[0, 0, 0, 1, 0x42, 0x01, 0x02]));
+
+        // No following start code has arrived yet, so the NAL isn't emitted.
+        assert!(splitter.next().is_none());
+
+        splitter.push(Bytes::from_static(&[0, 0, 1, 0x44]));
+
+        let nal = splitter.next().unwrap();
+        assert_eq!(nal.start_code, AnnexBStartCode::B4);
+        assert_eq!(&nal.full[nal.payload_range.clone()], &[0x42, 0x01, 0x02]);
+        assert!(splitter.next().is_none());
+    }
+
+    #[test]
+    fn test_streaming_nal_split_across_pushes() {
+        let mut splitter = AnnexBSplitter::new_streaming();
+        splitter.push(Bytes::from_static(&[0, 0, 0, 1, 0x42, 0x01]));
+        splitter.push(Bytes::from_static(&[0x02, 0x03, 0, 0, 1, 0x44]));
+
+        let nal = splitter.next().unwrap();
+        assert_eq!(nal.start_code, AnnexBStartCode::B4);
+        assert_eq!(
+            &nal.full[nal.payload_range.clone()],
+            &[0x42, 0x01, 0x02, 0x03]
+        );
+    }
+
+    #[test]
+    fn test_streaming_start_code_split_across_pushes() {
+        let mut splitter = AnnexBSplitter::new_streaming();
+        splitter.push(Bytes::from_static(&[0, 0, 0, 1, 0x42, 0, 0]));
+        splitter.push(Bytes::from_static(&[0, 1, 0x44]));
+
+        let nal = splitter.next().unwrap();
+        assert_eq!(nal.start_code, AnnexBStartCode::B4);
+        assert_eq!(&nal.full[nal.payload_range.clone()], &[0x42]);
+    }
+
+    #[test]
+    fn test_streaming_flush_emits_final_nal() {
+        let mut splitter = AnnexBSplitter::new_streaming();
+        splitter.push(Bytes::from_static(&[0, 0, 1, 0x44, 0x03, 0x04]));
+
+        assert!(splitter.next().is_none());
+
+        let nal = splitter.flush().unwrap();
+        assert_eq!(nal.start_code, AnnexBStartCode::B3);
+        assert_eq!(&nal.full[nal.payload_range.clone()], &[0x44, 0x03, 0x04]);
+        assert!(splitter.flush().is_none());
+    }
+
+    #[test]
+    fn test_h264_nal_unit_type_and_keyframe() {
+        // nal_ref_idc = 3, nal_unit_type = 5 (IDR slice)
+        let data = Bytes::from_static(&[0, 0, 1, 0x65, 0xAA, 0xBB]);
+        let mut splitter = AnnexBSplitter::new(data);
+        let nal = splitter.next().unwrap();
+
+        assert_eq!(
+            nal.nal_unit_type(AnnexBCodec::H264),
+            NalUnitType::H264(H264NalUnitType::IdrSlice)
+        );
+        assert!(nal.is_keyframe(AnnexBCodec::H264));
+        assert!(!nal.is_parameter_set(AnnexBCodec::H264));
+    }
+
+    #[test]
+    fn test_h264_sps_is_parameter_set() {
+        // nal_unit_type = 7 (SPS)
+        let data = Bytes::from_static(&[0, 0, 0, 1, 0x67, 0x42, 0x00]);
+        let mut splitter = AnnexBSplitter::new(data);
+        let nal = splitter.next().unwrap();
+
+        assert_eq!(
+            nal.nal_unit_type(AnnexBCodec::H264),
+            NalUnitType::H264(H264NalUnitType::Sps)
+        );
+        assert!(nal.is_parameter_set(AnnexBCodec::H264));
+        assert!(!nal.is_keyframe(AnnexBCodec::H264));
+    }
+
+    #[test]
+    fn test_h265_idr_is_keyframe() {
+        // nal_unit_type = 19 (IDR_W_RADL) -> byte0 = 19 << 1 = 0x26
+        let data = Bytes::from_static(&[0, 0, 1, 0x26, 0x01, 0xAA, 0xBB]);
+        let mut splitter = AnnexBSplitter::new(data);
+        let nal = splitter.next().unwrap();
+
+        assert_eq!(
+            nal.nal_unit_type(AnnexBCodec::H265),
+            NalUnitType::H265(H265NalUnitType::IdrWRadl)
+        );
+        assert!(nal.is_keyframe(AnnexBCodec::H265));
+    }
+
+    #[test]
+    fn test_h265_vps_is_parameter_set() {
+        // nal_unit_type = 32 (VPS_NUT) -> byte0 = 32 << 1 = 0x40
+        let data = Bytes::from_static(&[0, 0, 1, 0x40, 0x01, 0x0C]);
+        let mut splitter = AnnexBSplitter::new(data);
+        let nal = splitter.next().unwrap();
+
+        assert_eq!(
+            nal.nal_unit_type(AnnexBCodec::H265),
+            NalUnitType::H265(H265NalUnitType::VpsNut)
+        );
+        assert!(nal.is_parameter_set(AnnexBCodec::H265));
+    }
+
+    #[test]
+    fn test_rbsp_strips_emulation_prevention_bytes() {
+        // H.264 NAL header (1 byte) followed by a payload containing an
+        // emulation-prevented 00 00 00 sequence (00 00 03 00).
+        let data = Bytes::from_static(&[0, 0, 1, 0x65, 0x00, 0x00, 0x03, 0x00, 0xFF]);
+        let mut splitter = AnnexBSplitter::new(data);
+        let nal = splitter.next().unwrap();
+
+        assert_eq!(nal.rbsp(AnnexBCodec::H264), vec![0x00, 0x00, 0x00, 0xFF]);
+    }
 }
@@ -0,0 +1,250 @@
+use std::ops::Range;
+
+use bytes::Bytes;
+
+/// `obu_type` values relevant to a WebRTC sender (AV1 spec section 6.2.2).
+/// Anything not called out explicitly is kept as `Other`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObuType {
+    SequenceHeader,
+    TemporalDelimiter,
+    FrameHeader,
+    TileGroup,
+    Metadata,
+    Frame,
+    RedundantFrameHeader,
+    TileList,
+    Padding,
+    Other(u8),
+}
+
+impl ObuType {
+    fn from_raw(value: u8) -> Self {
+        match value {
+            1 => Self::SequenceHeader,
+            2 => Self::TemporalDelimiter,
+            3 => Self::FrameHeader,
+            4 => Self::TileGroup,
+            5 => Self::Metadata,
+            6 => Self::Frame,
+            7 => Self::RedundantFrameHeader,
+            8 => Self::TileList,
+            15 => Self::Padding,
+            other => Self::Other(other),
+        }
+    }
+}
+
+pub struct ObuData {
+    pub obu_type: ObuType,
+    /// `temporal_id` from the optional extension header, or 0 if absent.
+    pub temporal_id: u8,
+    /// `spatial_id` from the optional extension header, or 0 if absent.
+    pub spatial_id: u8,
+    pub header_range: Range<usize>,
+    pub payload_range: Range<usize>,
+    pub full: Bytes,
+}
+
+/// Splits an AV1 `obu_stream`/temporal-unit-aligned stream into individual
+/// OBUs, without copying: each [`ObuData`] is a zero-copy slice of the
+/// `Bytes` the splitter was given.
+///
+/// Unlike Annex B, AV1 doesn't use start codes: every OBU begins with an
+/// `obu_header` and, when `obu_has_size_field` is set, a LEB128-encoded
+/// `obu_size` that says exactly how many payload bytes follow. This mirrors
+/// how AV1 RTP depayloaders walk an OBU stream to find temporal-unit
+/// boundaries and sequence headers.
+pub struct ObuSplitter {
+    data: Bytes,
+    offset: usize,
+}
+
+impl ObuSplitter {
+    pub fn new(data: Bytes) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    pub fn reset(&mut self, data: Bytes) {
+        self.data = data;
+        self.offset = 0;
+    }
+
+    pub fn next(&mut self) -> Option<ObuData> {
+        if self.offset >= self.data.len() {
+            return None;
+        }
+
+        let start = self.offset;
+        let mut pos = start;
+
+        let obu_header = *self.data.get(pos)?;
+        pos += 1;
+
+        let obu_type = ObuType::from_raw((obu_header >> 3) & 0x0F);
+        let extension_flag = (obu_header >> 2) & 0x01 != 0;
+        let has_size_field = (obu_header >> 1) & 0x01 != 0;
+
+        let (temporal_id, spatial_id) = if extension_flag {
+            let extension_header = *self.data.get(pos)?;
+            pos += 1;
+            (
+                (extension_header >> 5) & 0x07,
+                (extension_header >> 3) & 0x03,
+            )
+        } else {
+            (0, 0)
+        };
+
+        let payload_len = if has_size_field {
+            let (size, leb_len) = read_leb128(&self.data[pos..])?;
+            pos += leb_len;
+            size as usize
+        } else {
+            // No size field: this OBU runs to the end of what we were given,
+            // as is the case for the last OBU of a low-overhead bitstream.
+            self.data.len() - pos
+        };
+
+        let header_len = pos - start;
+        let end = pos.checked_add(payload_len)?;
+        if end > self.data.len() {
+            // Truncated OBU: there isn't enough data to honor obu_size.
+            self.offset = self.data.len();
+            return None;
+        }
+
+        let full = self.data.slice(start..end);
+        self.offset = end;
+
+        Some(ObuData {
+            obu_type,
+            temporal_id,
+            spatial_id,
+            header_range: 0..header_len,
+            payload_range: header_len..(end - start),
+            full,
+        })
+    }
+}
+
+/// Decodes an unsigned LEB128 value (AV1 spec section 4.10.5), returning the
+/// value and how many bytes it occupied. AV1 caps `leb128` at 8 bytes.
+fn read_leb128(data: &[u8]) -> Option<(u64, usize)> {
+    let mut value: u64 = 0;
+
+    for (i, &byte) in data.iter().enumerate().take(8) {
+        value |= u64::from(byte & 0x7F) << (i * 7);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_obu_sequence_header_basic() {
+        // obu_header: type=1 (sequence header), no extension, has_size_field=1
+        // obu_size = 2 (leb128), payload = [0xAA, 0xBB]
+        let data = Bytes::from_static(&[0b0000_1010, 0x02, 0xAA, 0xBB]);
+        let mut splitter = ObuSplitter::new(data);
+
+        let obu = splitter.next().unwrap();
+        assert_eq!(obu.obu_type, ObuType::SequenceHeader);
+        assert_eq!(obu.temporal_id, 0);
+        assert_eq!(obu.spatial_id, 0);
+        assert_eq!(&obu.full[obu.payload_range.clone()], &[0xAA, 0xBB]);
+        assert!(splitter.next().is_none());
+    }
+
+    #[test]
+    fn test_obu_temporal_delimiter_empty_payload() {
+        // obu_header: type=2 (temporal delimiter), has_size_field=1, obu_size=0
+        let data = Bytes::from_static(&[0b0001_0010, 0x00]);
+        let mut splitter = ObuSplitter::new(data);
+
+        let obu = splitter.next().unwrap();
+        assert_eq!(obu.obu_type, ObuType::TemporalDelimiter);
+        assert!(obu.full[obu.payload_range.clone()].is_empty());
+    }
+
+    #[test]
+    fn test_obu_with_extension_header() {
+        // obu_header: type=6 (frame), extension_flag=1, has_size_field=1
+        // extension_header: temporal_id=2, spatial_id=1
+        // obu_size = 1, payload = [0xFF]
+        let extension_header = (2 << 5) | (1 << 3);
+        let data = Bytes::from(vec![0b0011_0110, extension_header, 0x01, 0xFF]);
+        let mut splitter = ObuSplitter::new(data);
+
+        let obu = splitter.next().unwrap();
+        assert_eq!(obu.obu_type, ObuType::Frame);
+        assert_eq!(obu.temporal_id, 2);
+        assert_eq!(obu.spatial_id, 1);
+        assert_eq!(&obu.full[obu.payload_range.clone()], &[0xFF]);
+    }
+
+    #[test]
+    fn test_obu_multiple_obus_in_sequence() {
+        let data = Bytes::from_static(&[
+            0b0000_1010, 0x01, 0xAA, // sequence header, size 1
+            0b0001_0010, 0x00, // temporal delimiter, size 0
+            0b0011_0010, 0x02, 0xBB, 0xCC, // frame, size 2
+        ]);
+        let mut splitter = ObuSplitter::new(data);
+
+        let obu1 = splitter.next().unwrap();
+        assert_eq!(obu1.obu_type, ObuType::SequenceHeader);
+        assert_eq!(&obu1.full[obu1.payload_range.clone()], &[0xAA]);
+
+        let obu2 = splitter.next().unwrap();
+        assert_eq!(obu2.obu_type, ObuType::TemporalDelimiter);
+        assert!(obu2.full[obu2.payload_range.clone()].is_empty());
+
+        let obu3 = splitter.next().unwrap();
+        assert_eq!(obu3.obu_type, ObuType::Frame);
+        assert_eq!(&obu3.full[obu3.payload_range.clone()], &[0xBB, 0xCC]);
+
+        assert!(splitter.next().is_none());
+    }
+
+    #[test]
+    fn test_obu_without_size_field_consumes_rest() {
+        // has_size_field=0: this OBU's payload is everything left in the buffer.
+        let data = Bytes::from_static(&[0b0000_1000, 0xAA, 0xBB, 0xCC]);
+        let mut splitter = ObuSplitter::new(data);
+
+        let obu = splitter.next().unwrap();
+        assert_eq!(obu.obu_type, ObuType::SequenceHeader);
+        assert_eq!(&obu.full[obu.payload_range.clone()], &[0xAA, 0xBB, 0xCC]);
+        assert!(splitter.next().is_none());
+    }
+
+    #[test]
+    fn test_obu_multibyte_leb128_size() {
+        // obu_size = 200, which needs two leb128 bytes: 0xC8, 0x01
+        let mut data = vec![0b0000_1010, 0xC8, 0x01];
+        data.extend(std::iter::repeat(0x7A).take(200));
+        let mut splitter = ObuSplitter::new(Bytes::from(data));
+
+        let obu = splitter.next().unwrap();
+        assert_eq!(obu.obu_type, ObuType::SequenceHeader);
+        assert_eq!(obu.full[obu.payload_range.clone()].len(), 200);
+        assert!(splitter.next().is_none());
+    }
+
+    #[test]
+    fn test_obu_truncated_size_returns_none() {
+        // obu_size claims 10 bytes of payload, but only 2 are present.
+        let data = Bytes::from_static(&[0b0000_1010, 0x0A, 0xAA, 0xBB]);
+        let mut splitter = ObuSplitter::new(data);
+
+        assert!(splitter.next().is_none());
+    }
+}